@@ -2,7 +2,10 @@ use super::ParquetProcessingResult;
 use crate::{
     bq_analytics::gcs_handler::upload_parquet_to_gcs,
     gap_detectors::ProcessingResult,
-    utils::counters::{PARQUET_HANDLER_BUFFER_SIZE, PARQUET_STRUCT_SIZE},
+    utils::{
+        counters::{DRY_RUN_SKIPPED_ROWS_COUNT, PARQUET_HANDLER_BUFFER_SIZE, PARQUET_STRUCT_SIZE},
+        database::is_dry_run,
+    },
 };
 use ahash::AHashMap;
 use allocative::Allocative;
@@ -182,13 +185,26 @@ where
                 end_version = end_version,
                 "Max buffer size reached, uploading to GCS."
             );
-            let upload_result = upload_parquet_to_gcs(
-                gcs_client,
-                &new_file_path,
-                ParquetType::TABLE_NAME,
-                &self.bucket_name,
-            )
-            .await;
+            let upload_result = if is_dry_run() {
+                // Same as the DB writes, so a dry run never publishes files to the bucket
+                debug!(
+                    table_name = ParquetType::TABLE_NAME,
+                    num_rows = struct_buffer.len(),
+                    "Dry run, skipping GCS upload"
+                );
+                DRY_RUN_SKIPPED_ROWS_COUNT
+                    .with_label_values(&[ParquetType::TABLE_NAME])
+                    .inc_by(struct_buffer.len() as u64);
+                Ok(())
+            } else {
+                upload_parquet_to_gcs(
+                    gcs_client,
+                    &new_file_path,
+                    ParquetType::TABLE_NAME,
+                    &self.bucket_name,
+                )
+                .await
+            };
             self.buffer_size_bytes = 0;
             remove_file(&new_file_path)?;
 
@@ -2,12 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    gap_detectors::DEFAULT_GAP_DETECTION_BATCH_SIZE, processors::ProcessorConfig,
-    transaction_filter::TransactionFilter, worker::Worker,
+    gap_detectors::DEFAULT_GAP_DETECTION_BATCH_SIZE,
+    processors::ProcessorConfig,
+    transaction_filter::TransactionFilter,
+    worker::{Worker, BUFFER_SIZE},
 };
 use ahash::AHashMap;
 use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use server_framework::RunnableConfig;
 use std::{collections::HashSet, time::Duration};
 use url::Url;
@@ -27,6 +29,9 @@ pub struct IndexerGrpcProcessorConfig {
     pub auth_token: String,
     // Version to start indexing from
     pub starting_version: Option<u64>,
+    // If there is no starting version in the config or the DB, start from the version the
+    // indexed module was published at instead of 0
+    pub starting_version_auto_detect: Option<StartingVersionAutoDetectConfig>,
     // Version to end indexing at
     pub ending_version: Option<u64>,
     // Number of tasks waiting to pull transaction batches from the channel and process them
@@ -55,6 +60,21 @@ pub struct IndexerGrpcProcessorConfig {
     // String vector for deprecated tables to skip db writes
     #[serde(default)]
     pub deprecated_tables: HashSet<String>,
+    // Parse and build models as usual but skip migrations and all DB writes
+    #[serde(default)]
+    pub dry_run: bool,
+    // Run pending migrations on startup. If disabled, the processor refuses to start unless the
+    // DB schema already has every migration this binary was built with
+    #[serde(default = "IndexerGrpcProcessorConfig::default_auto_migrate")]
+    pub auto_migrate: bool,
+    // Adjust the insert chunk size per model based on observed insert latency, never going above
+    // the size from per_table_chunk_sizes (or the default)
+    #[serde(default)]
+    pub enable_chunk_size_auto_tuning: bool,
+    // Fire a webhook when no batch has been committed for a while
+    pub stall_alert: Option<StallAlertConfig>,
+    // Throttle GRPC fetching while the DB is falling behind
+    pub backpressure: Option<BackpressureConfig>,
 }
 
 impl IndexerGrpcProcessorConfig {
@@ -80,6 +100,11 @@ impl IndexerGrpcProcessorConfig {
     pub const fn default_grpc_response_item_timeout_in_secs() -> u64 {
         60
     }
+
+    /// Keep running migrations on startup by default so existing deployments don't change behavior
+    pub const fn default_auto_migrate() -> bool {
+        true
+    }
 }
 
 #[async_trait::async_trait]
@@ -92,6 +117,7 @@ impl RunnableConfig for IndexerGrpcProcessorConfig {
             self.grpc_http2_config.clone(),
             self.auth_token.clone(),
             self.starting_version,
+            self.starting_version_auto_detect.clone(),
             self.ending_version,
             self.number_concurrent_processing_tasks,
             self.db_pool_size,
@@ -103,6 +129,11 @@ impl RunnableConfig for IndexerGrpcProcessorConfig {
             self.transaction_filter.clone(),
             self.grpc_response_item_timeout_in_secs,
             self.deprecated_tables.clone(),
+            self.dry_run,
+            self.auto_migrate,
+            self.enable_chunk_size_auto_tuning,
+            self.stall_alert.clone(),
+            self.backpressure.clone(),
         )
         .await
         .context("Failed to build worker")?;
@@ -122,6 +153,77 @@ impl RunnableConfig for IndexerGrpcProcessorConfig {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StartingVersionAutoDetectConfig {
+    /// Fullnode REST API used to look up the publish transaction, e.g.
+    /// https://fullnode.mainnet.aptoslabs.com/v1
+    pub fullnode_rest_url: Url,
+    /// Account that published the module(s) this processor cares about.
+    pub module_address: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StallAlertConfig {
+    /// Webhook receiving a Slack compatible `{"text": ...}` payload when the processor stalls
+    /// and again when it recovers.
+    pub webhook_url: Url,
+    /// Seconds without a committed batch before alerting. Defaults to 300.
+    #[serde(default = "StallAlertConfig::default_stall_threshold_secs")]
+    pub stall_threshold_secs: u64,
+}
+
+impl StallAlertConfig {
+    pub const fn default_stall_threshold_secs() -> u64 {
+        300
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackpressureConfig {
+    /// The DB counts as falling behind when the last batch took longer than this to insert,
+    /// or when every connection in the pool is in use. Defaults to 10.
+    #[serde(default = "BackpressureConfig::default_max_db_insertion_latency_in_secs")]
+    pub max_db_insertion_latency_in_secs: f64,
+    /// Number of fetched batches allowed to queue up while the DB is falling behind before
+    /// fetching waits. Defaults to 10. Must be below the channel size, otherwise the channel
+    /// fills up before throttling would kick in.
+    #[serde(
+        default = "BackpressureConfig::default_max_queued_batches_under_pressure",
+        deserialize_with = "BackpressureConfig::deserialize_max_queued_batches_under_pressure"
+    )]
+    pub max_queued_batches_under_pressure: usize,
+}
+
+impl BackpressureConfig {
+    pub const fn default_max_db_insertion_latency_in_secs() -> f64 {
+        10.0
+    }
+
+    pub const fn default_max_queued_batches_under_pressure() -> usize {
+        10
+    }
+
+    fn deserialize_max_queued_batches_under_pressure<'de, D>(
+        deserializer: D,
+    ) -> Result<usize, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = usize::deserialize(deserializer)?;
+        if value == 0 || value >= BUFFER_SIZE {
+            return Err(serde::de::Error::custom(format!(
+                "max_queued_batches_under_pressure must be between 1 and {}, got {}",
+                BUFFER_SIZE - 1,
+                value
+            )));
+        }
+        Ok(value)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 #[serde(default)]
@@ -160,3 +262,24 @@ impl Default for IndexerGrpcHttp2Config {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backpressure_config_rejects_unreachable_queue_size() {
+        let parse = |max_queued_batches_under_pressure: usize| {
+            serde_json::from_value::<BackpressureConfig>(serde_json::json!({
+                "max_queued_batches_under_pressure": max_queued_batches_under_pressure,
+            }))
+        };
+        assert_eq!(parse(10).unwrap().max_queued_batches_under_pressure, 10);
+        assert!(parse(BUFFER_SIZE - 1).is_ok());
+        assert!(parse(BUFFER_SIZE).is_err());
+        assert!(parse(0).is_err());
+
+        let config = serde_json::from_value::<BackpressureConfig>(serde_json::json!({})).unwrap();
+        assert_eq!(config.max_queued_batches_under_pressure, 10);
+    }
+}
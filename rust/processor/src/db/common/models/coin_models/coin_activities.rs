@@ -25,8 +25,8 @@ use crate::{
     utils::{
         counters::PROCESSOR_UNKNOWN_TYPE_COUNT,
         util::{
-            get_entry_function_from_user_request, standardize_address, u64_to_bigdecimal,
-            APTOS_COIN_TYPE_STR,
+            get_entry_function_from_user_request, get_standardized_entry_function,
+            standardize_address, u64_to_bigdecimal, APTOS_COIN_TYPE_STR,
         },
     },
 };
@@ -65,6 +65,8 @@ pub struct CoinActivity {
     pub event_index: Option<i64>,
     pub gas_fee_payer_address: Option<String>,
     pub storage_refund_amount: BigDecimal,
+    // Entry function id as received from upstream, before address standardization
+    pub raw_entry_function_id_str: Option<String>,
 }
 
 impl CoinActivity {
@@ -253,12 +255,13 @@ impl CoinActivity {
             activity_type: event_type.to_string(),
             is_gas_fee: false,
             is_transaction_success: true,
-            entry_function_id_str: entry_function_id_str.clone(),
+            entry_function_id_str: get_standardized_entry_function(entry_function_id_str),
             block_height,
             transaction_timestamp,
             event_index: Some(event_index),
             gas_fee_payer_address: None,
             storage_refund_amount: BigDecimal::zero(),
+            raw_entry_function_id_str: entry_function_id_str.clone(),
         }
     }
 
@@ -291,7 +294,7 @@ impl CoinActivity {
             activity_type: GAS_FEE_EVENT.to_string(),
             is_gas_fee: true,
             is_transaction_success: txn_info.success,
-            entry_function_id_str: entry_function_id_str.clone(),
+            entry_function_id_str: get_standardized_entry_function(entry_function_id_str),
             block_height,
             transaction_timestamp,
             event_index: Some(BURN_GAS_EVENT_INDEX),
@@ -299,6 +302,7 @@ impl CoinActivity {
             storage_refund_amount: fee_statement
                 .map(|fs| u64_to_bigdecimal(fs.storage_fee_refund_octas))
                 .unwrap_or(BigDecimal::zero()),
+            raw_entry_function_id_str: entry_function_id_str.clone(),
         }
     }
 }
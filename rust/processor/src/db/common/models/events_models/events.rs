@@ -5,7 +5,7 @@
 
 use crate::{
     schema::events,
-    utils::util::{standardize_address, truncate_str},
+    utils::util::{standardize_address, truncate_str, u64_to_i64_saturating},
 };
 use aptos_protos::transaction::v1::Event as EventPB;
 use field_count::FieldCount;
@@ -41,8 +41,11 @@ impl Event {
             account_address: standardize_address(
                 event.key.as_ref().unwrap().account_address.as_str(),
             ),
-            creation_number: event.key.as_ref().unwrap().creation_number as i64,
-            sequence_number: event.sequence_number as i64,
+            creation_number: u64_to_i64_saturating(
+                event.key.as_ref().unwrap().creation_number,
+                "events.creation_number",
+            ),
+            sequence_number: u64_to_i64_saturating(event.sequence_number, "events.sequence_number"),
             transaction_version,
             transaction_block_height,
             type_: t.to_string(),
@@ -16,7 +16,7 @@ use crate::{
         token_v2_models::v2_token_utils::TokenStandard,
     },
     schema::fungible_asset_activities,
-    utils::util::standardize_address,
+    utils::util::{get_standardized_entry_function, standardize_address},
 };
 use ahash::AHashMap;
 use anyhow::Context;
@@ -56,6 +56,8 @@ pub struct FungibleAssetActivity {
     pub token_standard: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
     pub storage_refund_amount: BigDecimal,
+    // Entry function id as received from upstream, before address standardization
+    pub raw_entry_function_id_str: Option<String>,
 }
 
 impl FungibleAssetActivity {
@@ -99,11 +101,12 @@ impl FungibleAssetActivity {
                     is_gas_fee: false,
                     gas_fee_payer_address: None,
                     is_transaction_success: true,
-                    entry_function_id_str: entry_function_id_str.clone(),
+                    entry_function_id_str: get_standardized_entry_function(entry_function_id_str),
                     block_height,
                     token_standard: TokenStandard::V2.to_string(),
                     transaction_timestamp: txn_timestamp,
                     storage_refund_amount: BigDecimal::zero(),
+                    raw_entry_function_id_str: entry_function_id_str.clone(),
                 }));
             }
         }
@@ -157,11 +160,12 @@ impl FungibleAssetActivity {
                 is_gas_fee: false,
                 gas_fee_payer_address: None,
                 is_transaction_success: true,
-                entry_function_id_str: entry_function_id_str.clone(),
+                entry_function_id_str: get_standardized_entry_function(entry_function_id_str),
                 block_height,
                 token_standard: TokenStandard::V1.to_string(),
                 transaction_timestamp,
                 storage_refund_amount: BigDecimal::zero(),
+                raw_entry_function_id_str: entry_function_id_str.clone(),
             }))
         } else {
             Ok(None)
@@ -209,6 +213,7 @@ impl FungibleAssetActivity {
             token_standard: TokenStandard::V1.to_string(),
             transaction_timestamp,
             storage_refund_amount: v1_activity.storage_refund_amount,
+            raw_entry_function_id_str: v1_activity.raw_entry_function_id_str,
         }
     }
 }
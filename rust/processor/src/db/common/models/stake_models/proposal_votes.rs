@@ -9,7 +9,7 @@ use crate::{
     schema::proposal_votes,
     utils::{
         counters::PROCESSOR_UNKNOWN_TYPE_COUNT,
-        util::{parse_timestamp, standardize_address},
+        util::{parse_timestamp, standardize_address, u64_to_i64_saturating},
     },
 };
 use aptos_protos::transaction::v1::{transaction::TxnData, Transaction};
@@ -55,7 +55,10 @@ impl ProposalVote {
                 {
                     proposal_votes.push(Self {
                         transaction_version: txn_version,
-                        proposal_id: ev.proposal_id as i64,
+                        proposal_id: u64_to_i64_saturating(
+                            ev.proposal_id,
+                            "proposal_votes.proposal_id",
+                        ),
                         voter_address: standardize_address(&ev.voter),
                         staking_pool_address: standardize_address(&ev.stake_pool),
                         num_votes: ev.num_votes.clone(),
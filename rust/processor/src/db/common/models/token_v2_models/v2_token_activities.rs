@@ -12,7 +12,7 @@ use crate::{
         token_models::token_utils::{TokenDataIdType, TokenEvent},
     },
     schema::token_activities_v2,
-    utils::util::standardize_address,
+    utils::util::{get_standardized_entry_function, standardize_address},
 };
 use aptos_protos::transaction::v1::Event;
 use bigdecimal::{BigDecimal, One, Zero};
@@ -38,6 +38,8 @@ pub struct TokenActivityV2 {
     pub token_standard: String,
     pub is_fungible_v2: Option<bool>,
     pub transaction_timestamp: chrono::NaiveDateTime,
+    // Entry function id as received from upstream, before address standardization
+    pub raw_entry_function_id_str: Option<String>,
 }
 
 /// A simplified TokenActivity (excluded common fields) to reduce code duplication
@@ -148,10 +150,11 @@ impl TokenActivityV2 {
                     token_amount: token_activity_helper.token_amount,
                     before_value: token_activity_helper.before_value,
                     after_value: token_activity_helper.after_value,
-                    entry_function_id_str: entry_function_id_str.clone(),
+                    entry_function_id_str: get_standardized_entry_function(entry_function_id_str),
                     token_standard: TokenStandard::V2.to_string(),
                     is_fungible_v2: None,
                     transaction_timestamp: txn_timestamp,
+                    raw_entry_function_id_str: entry_function_id_str.clone(),
                 }));
             } else {
                 // If the object metadata isn't found in the transaction, then the token was burnt.
@@ -176,10 +179,11 @@ impl TokenActivityV2 {
                     token_amount: BigDecimal::one(),
                     before_value: None,
                     after_value: None,
-                    entry_function_id_str: entry_function_id_str.clone(),
+                    entry_function_id_str: get_standardized_entry_function(entry_function_id_str),
                     token_standard: TokenStandard::V2.to_string(),
                     is_fungible_v2: None,
                     transaction_timestamp: txn_timestamp,
+                    raw_entry_function_id_str: entry_function_id_str.clone(),
                 }));
             }
         }
@@ -268,10 +272,11 @@ impl TokenActivityV2 {
                 token_amount: token_activity_helper.token_amount,
                 before_value: None,
                 after_value: None,
-                entry_function_id_str: entry_function_id_str.clone(),
+                entry_function_id_str: get_standardized_entry_function(entry_function_id_str),
                 token_standard: TokenStandard::V1.to_string(),
                 is_fungible_v2: None,
                 transaction_timestamp: txn_timestamp,
+                raw_entry_function_id_str: entry_function_id_str.clone(),
             }));
         }
         Ok(None)
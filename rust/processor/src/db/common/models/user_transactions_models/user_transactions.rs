@@ -11,8 +11,8 @@ use super::signatures::Signature;
 use crate::{
     schema::user_transactions,
     utils::util::{
-        get_entry_function_from_user_request, parse_timestamp, standardize_address,
-        u64_to_bigdecimal,
+        get_entry_function_from_user_request, get_standardized_entry_function, parse_timestamp,
+        standardize_address, u64_to_bigdecimal, u64_to_i64_saturating,
     },
 };
 use aptos_protos::{
@@ -38,6 +38,8 @@ pub struct UserTransaction {
     pub timestamp: chrono::NaiveDateTime,
     pub entry_function_id_str: String,
     pub epoch: i64,
+    // Entry function id as received from upstream, before address standardization
+    pub raw_entry_function_id_str: Option<String>,
 }
 
 impl UserTransaction {
@@ -52,6 +54,7 @@ impl UserTransaction {
             .request
             .as_ref()
             .expect("Sends is not present in user txn");
+        let raw_entry_function_id_str = get_entry_function_from_user_request(user_request);
         (
             Self {
                 version,
@@ -65,7 +68,10 @@ impl UserTransaction {
                     .map(Signature::get_signature_type)
                     .unwrap_or_default(),
                 sender: standardize_address(&user_request.sender),
-                sequence_number: user_request.sequence_number as i64,
+                sequence_number: u64_to_i64_saturating(
+                    user_request.sequence_number,
+                    "user_transactions.sequence_number",
+                ),
                 max_gas_amount: u64_to_bigdecimal(user_request.max_gas_amount),
                 expiration_timestamp_secs: parse_timestamp(
                     user_request
@@ -76,9 +82,10 @@ impl UserTransaction {
                 ),
                 gas_unit_price: u64_to_bigdecimal(user_request.gas_unit_price),
                 timestamp: parse_timestamp(timestamp, version),
-                entry_function_id_str: get_entry_function_from_user_request(user_request)
+                entry_function_id_str: get_standardized_entry_function(&raw_entry_function_id_str)
                     .unwrap_or_default(),
                 epoch,
+                raw_entry_function_id_str,
             },
             Self::get_signatures(user_request, version, block_height),
         )
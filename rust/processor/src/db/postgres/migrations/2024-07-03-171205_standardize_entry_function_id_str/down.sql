@@ -0,0 +1,30 @@
+-- This file should undo anything in `up.sql`
+DROP PROCEDURE IF EXISTS backfill_standardized_entry_function_id_str(BIGINT);
+DROP FUNCTION IF EXISTS standardize_entry_function_id_str(VARCHAR);
+-- The view can't drop a column through CREATE OR REPLACE, so recreate it
+DROP VIEW IF EXISTS legacy_migration_v1.coin_activities;
+CREATE VIEW legacy_migration_v1.coin_activities AS
+SElECT transaction_version,
+    owner_address as event_account_address,
+    -- these two below are mildly concerning
+    0 as event_creation_number,
+    0 as event_sequence_number,
+    owner_address,
+    asset_type AS coin_type,
+    amount,
+    "type" AS activity_type,
+    is_gas_fee,
+    is_transaction_success,
+    entry_function_id_str,
+    block_height,
+    transaction_timestamp,
+    inserted_at,
+    event_index,
+    gas_fee_payer_address,
+    storage_refund_amount
+FROM public.fungible_asset_activities
+WHERE token_standard = 'v1';
+ALTER TABLE user_transactions DROP COLUMN IF EXISTS raw_entry_function_id_str;
+ALTER TABLE coin_activities DROP COLUMN IF EXISTS raw_entry_function_id_str;
+ALTER TABLE fungible_asset_activities DROP COLUMN IF EXISTS raw_entry_function_id_str;
+ALTER TABLE token_activities_v2 DROP COLUMN IF EXISTS raw_entry_function_id_str;
@@ -0,0 +1,97 @@
+-- Your SQL goes here
+-- Every table storing entry_function_id_str stores it with a standardized address, and keeps the
+-- id as received from upstream in raw_entry_function_id_str.
+ALTER TABLE user_transactions
+ADD COLUMN IF NOT EXISTS raw_entry_function_id_str VARCHAR(1000);
+ALTER TABLE coin_activities
+ADD COLUMN IF NOT EXISTS raw_entry_function_id_str VARCHAR(1000);
+ALTER TABLE fungible_asset_activities
+ADD COLUMN IF NOT EXISTS raw_entry_function_id_str VARCHAR(1000);
+ALTER TABLE token_activities_v2
+ADD COLUMN IF NOT EXISTS raw_entry_function_id_str VARCHAR(1000);
+-- Expose the raw id through the legacy view as well
+CREATE OR REPLACE VIEW legacy_migration_v1.coin_activities AS
+SElECT transaction_version,
+    owner_address as event_account_address,
+    -- these two below are mildly concerning
+    0 as event_creation_number,
+    0 as event_sequence_number,
+    owner_address,
+    asset_type AS coin_type,
+    amount,
+    "type" AS activity_type,
+    is_gas_fee,
+    is_transaction_success,
+    entry_function_id_str,
+    block_height,
+    transaction_timestamp,
+    inserted_at,
+    event_index,
+    gas_fee_payer_address,
+    storage_refund_amount,
+    raw_entry_function_id_str
+FROM public.fungible_asset_activities
+WHERE token_standard = 'v1';
+-- Standardizes the address of an entry function id the same way the processor does, e.g.
+-- 0x1::coin::transfer -> 0x0000000000000000000000000000000000000000000000000000000000000001::coin::transfer
+CREATE OR REPLACE FUNCTION standardize_entry_function_id_str(entry_function_id_str VARCHAR) RETURNS VARCHAR AS $$
+SELECT CASE
+    WHEN entry_function_id_str LIKE '0x%::%' THEN '0x' || lpad(
+      substring(
+        split_part(entry_function_id_str, '::', 1)
+        FROM 3
+      ),
+      64,
+      '0'
+    ) || substring(
+      entry_function_id_str
+      FROM position('::' IN entry_function_id_str)
+    )
+    ELSE entry_function_id_str
+  END $$ LANGUAGE SQL IMMUTABLE;
+-- Backfills rows written before this migration in version ranges of batch_size, committing after
+-- each range so large tables aren't locked for long. Those are the rows with an entry function id
+-- but no raw id. Rows without an entry function (NULL or '') have nothing to standardize and keep a
+-- NULL raw id, same as the processor writes. Until this has run, rows written before the migration
+-- still have the short form.
+-- Rewriting existing rows here would hold a lock for each whole table, so run it separately,
+-- outside of a transaction block. It can be interrupted and called again:
+-- CALL backfill_standardized_entry_function_id_str();
+CREATE OR REPLACE PROCEDURE backfill_standardized_entry_function_id_str(batch_size BIGINT DEFAULT 100000) LANGUAGE plpgsql AS $$
+DECLARE backfill_table TEXT;
+version_column TEXT;
+start_version BIGINT;
+end_version BIGINT;
+BEGIN FOREACH backfill_table IN ARRAY ARRAY [
+  'user_transactions',
+  'coin_activities',
+  'fungible_asset_activities',
+  'token_activities_v2'
+] LOOP version_column := CASE
+  WHEN backfill_table = 'user_transactions' THEN 'version'
+  ELSE 'transaction_version'
+END;
+EXECUTE format(
+  'SELECT MIN(%2$I), MAX(%2$I) FROM %1$I
+    WHERE raw_entry_function_id_str IS NULL AND entry_function_id_str <> %3$L',
+  backfill_table,
+  version_column,
+  ''
+) INTO start_version,
+end_version;
+WHILE start_version <= end_version LOOP EXECUTE format(
+  'UPDATE %1$I
+    SET raw_entry_function_id_str = entry_function_id_str,
+      entry_function_id_str = left(standardize_entry_function_id_str(entry_function_id_str), 1000)
+    WHERE %2$I >= $1 AND %2$I < $1 + $2
+      AND raw_entry_function_id_str IS NULL AND entry_function_id_str <> %3$L',
+  backfill_table,
+  version_column,
+  ''
+) USING start_version,
+batch_size;
+COMMIT;
+start_version := start_version + batch_size;
+END LOOP;
+END LOOP;
+END $$;
@@ -126,6 +126,8 @@ diesel::table! {
         #[max_length = 66]
         gas_fee_payer_address -> Nullable<Varchar>,
         storage_refund_amount -> Numeric,
+        #[max_length = 1000]
+        raw_entry_function_id_str -> Nullable<Varchar>,
     }
 }
 
@@ -790,6 +792,8 @@ diesel::table! {
         transaction_timestamp -> Timestamp,
         inserted_at -> Timestamp,
         storage_refund_amount -> Numeric,
+        #[max_length = 1000]
+        raw_entry_function_id_str -> Nullable<Varchar>,
     }
 }
 
@@ -1062,6 +1066,8 @@ diesel::table! {
         is_fungible_v2 -> Nullable<Bool>,
         transaction_timestamp -> Timestamp,
         inserted_at -> Timestamp,
+        #[max_length = 1000]
+        raw_entry_function_id_str -> Nullable<Varchar>,
     }
 }
 
@@ -1250,6 +1256,8 @@ diesel::table! {
         entry_function_id_str -> Varchar,
         inserted_at -> Timestamp,
         epoch -> Int8,
+        #[max_length = 1000]
+        raw_entry_function_id_str -> Nullable<Varchar>,
     }
 }
 
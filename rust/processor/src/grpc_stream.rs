@@ -1,8 +1,9 @@
 use crate::utils::{
+    backpressure::Backpressure,
     counters::{
-        ProcessorStep, FETCHER_THREAD_CHANNEL_SIZE, LATEST_PROCESSED_VERSION,
-        NUM_TRANSACTIONS_FILTERED_OUT_COUNT, NUM_TRANSACTIONS_PROCESSED_COUNT,
-        PROCESSED_BYTES_COUNT, TRANSACTION_UNIX_TIMESTAMP,
+        ProcessorStep, FETCHER_THREAD_CHANNEL_SIZE, GRPC_BATCH_FETCH_LATENCY_IN_SECS,
+        LATEST_PROCESSED_VERSION, NUM_TRANSACTIONS_FILTERED_OUT_COUNT,
+        NUM_TRANSACTIONS_PROCESSED_COUNT, PROCESSED_BYTES_COUNT, TRANSACTION_UNIX_TIMESTAMP,
     },
     util::{timestamp_to_iso, timestamp_to_unixtime},
 };
@@ -45,6 +46,10 @@ pub struct TransactionsPBResponse {
     pub start_txn_timestamp: Option<Timestamp>,
     pub end_txn_timestamp: Option<Timestamp>,
     pub size_in_bytes: u64,
+    // Upstream stream metadata so batches can be cross-referenced with data service incidents
+    pub connection_id: String,
+    // Time spent waiting on the GRPC stream for the response this batch came from
+    pub fetch_duration_in_secs: f64,
 }
 
 pub fn grpc_request_builder(
@@ -316,6 +321,7 @@ pub async fn create_fetcher_loop(
     transaction_filter: crate::transaction_filter::TransactionFilter,
     // The number of transactions per protobuf batch
     pb_channel_txn_chunk_size: usize,
+    backpressure: Option<Backpressure>,
 ) {
     info!(
         processor_name = processor_name,
@@ -359,6 +365,39 @@ pub async fn create_fetcher_loop(
     let mut send_ma = MovingAverage::new(3000);
 
     loop {
+        if server_framework::is_indexing_paused() {
+            // Batches already in the channel still get processed. If the stream times out while
+            // paused, the usual reconnection logic below picks it back up.
+            info!(
+                processor_name = processor_name,
+                service_type = crate::worker::PROCESSOR_SERVICE_TYPE,
+                stream_address = indexer_grpc_data_service_address.to_string(),
+                connection_id,
+                next_version_to_fetch,
+                "[Parser] Indexing paused, waiting to be resumed"
+            );
+            while server_framework::is_indexing_paused() {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            info!(
+                processor_name = processor_name,
+                service_type = crate::worker::PROCESSOR_SERVICE_TYPE,
+                stream_address = indexer_grpc_data_service_address.to_string(),
+                connection_id,
+                next_version_to_fetch,
+                "[Parser] Indexing resumed"
+            );
+            grpc_channel_recv_latency = std::time::Instant::now();
+        }
+        if let Some(backpressure) = &backpressure {
+            if backpressure
+                .wait_for_capacity(&txn_sender, &processor_name)
+                .await
+            {
+                // Don't count time spent throttled as GRPC fetch time
+                grpc_channel_recv_latency = std::time::Instant::now();
+            }
+        }
         let is_success = match tokio::time::timeout(
             indexer_grpc_response_item_timeout_secs,
             resp_stream.next(),
@@ -450,6 +489,9 @@ pub async fn create_fetcher_loop(
                         NUM_TRANSACTIONS_PROCESSED_COUNT
                             .with_label_values(&[&processor_name, step, label, "-"])
                             .inc_by(end_version - start_version + 1);
+                        GRPC_BATCH_FETCH_LATENCY_IN_SECS
+                            .with_label_values(&[&processor_name])
+                            .set(duration_in_secs);
 
                         let txn_channel_send_latency = std::time::Instant::now();
 
@@ -464,6 +506,8 @@ pub async fn create_fetcher_loop(
                                 start_txn_timestamp,
                                 end_txn_timestamp,
                                 size_in_bytes,
+                                connection_id: connection_id.clone(),
+                                fetch_duration_in_secs: duration_in_secs,
                             };
 
                             match txn_sender.send(txn_pb).await {
@@ -501,6 +545,8 @@ pub async fn create_fetcher_loop(
                                     start_txn_timestamp: start_txn_timestamp.clone(),
                                     end_txn_timestamp: end_txn_timestamp.clone(),
                                     size_in_bytes,
+                                    connection_id: connection_id.clone(),
+                                    fetch_duration_in_secs: duration_in_secs,
                                 };
 
                                 match txn_sender.send(txn_pb).await {
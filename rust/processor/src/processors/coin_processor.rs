@@ -125,6 +125,7 @@ fn insert_coin_activities_query(
             .do_update()
             .set((
                 entry_function_id_str.eq(excluded(entry_function_id_str)),
+                raw_entry_function_id_str.eq(excluded(raw_entry_function_id_str)),
                 inserted_at.eq(excluded(inserted_at)),
             )),
         None,
@@ -103,6 +103,8 @@ fn insert_user_transactions_query(
             .do_update()
             .set((
                 expiration_timestamp_secs.eq(excluded(expiration_timestamp_secs)),
+                entry_function_id_str.eq(excluded(entry_function_id_str)),
+                raw_entry_function_id_str.eq(excluded(raw_entry_function_id_str)),
                 inserted_at.eq(excluded(inserted_at)),
             )),
         None,
@@ -0,0 +1,105 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Slows down GRPC fetching while the DB can't keep up, so fetched batches don't pile up in
+//! memory during Postgres incidents.
+
+use crate::{
+    config::BackpressureConfig,
+    grpc_stream::TransactionsPBResponse,
+    utils::{counters::FETCHER_THROTTLED, database::ArcDbPool},
+    worker::PROCESSOR_SERVICE_TYPE,
+};
+use kanal::AsyncSender;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::info;
+
+const THROTTLE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Shared between the processor tasks, which report how long their inserts take, and the
+/// fetcher, which waits while the DB is under pressure.
+#[derive(Clone)]
+pub struct Backpressure {
+    config: BackpressureConfig,
+    db_pool: ArcDbPool,
+    max_pool_size: u32,
+    last_db_insertion_latency_in_ms: Arc<AtomicU64>,
+}
+
+impl Backpressure {
+    pub fn new(config: BackpressureConfig, db_pool: ArcDbPool, max_pool_size: u32) -> Self {
+        Self {
+            config,
+            db_pool,
+            max_pool_size,
+            last_db_insertion_latency_in_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn record_db_insertion_latency(&self, db_insertion_duration_in_secs: f64) {
+        self.last_db_insertion_latency_in_ms.store(
+            (db_insertion_duration_in_secs * 1000.0) as u64,
+            Ordering::Relaxed,
+        );
+    }
+
+    fn is_db_under_pressure(&self) -> bool {
+        let last_db_insertion_latency_in_secs =
+            self.last_db_insertion_latency_in_ms.load(Ordering::Relaxed) as f64 / 1000.0;
+        let pool_state = self.db_pool.state();
+        let pool_saturated =
+            pool_state.idle_connections == 0 && pool_state.connections >= self.max_pool_size;
+        last_db_insertion_latency_in_secs > self.config.max_db_insertion_latency_in_secs
+            || pool_saturated
+    }
+
+    /// Only throttle once enough batches are queued up. With an empty channel the processor
+    /// tasks are idle, so holding back fetching would just stall (and the latency would never
+    /// get updated again).
+    fn should_throttle(&self, queued_batches: usize) -> bool {
+        queued_batches >= self.config.max_queued_batches_under_pressure
+            && self.is_db_under_pressure()
+    }
+
+    /// Waits until the DB recovers or the processor tasks drain the channel. Returns whether
+    /// fetching was throttled at all.
+    pub async fn wait_for_capacity(
+        &self,
+        txn_sender: &AsyncSender<TransactionsPBResponse>,
+        processor_name: &str,
+    ) -> bool {
+        if !self.should_throttle(txn_sender.len()) {
+            return false;
+        }
+        info!(
+            processor_name = processor_name,
+            service_type = PROCESSOR_SERVICE_TYPE,
+            channel_size = txn_sender.len(),
+            "[Parser] DB is falling behind, throttling GRPC fetching"
+        );
+        FETCHER_THROTTLED
+            .with_label_values(&[processor_name])
+            .set(1);
+        let throttle_time = Instant::now();
+        while self.should_throttle(txn_sender.len()) {
+            tokio::time::sleep(THROTTLE_CHECK_INTERVAL).await;
+        }
+        FETCHER_THROTTLED
+            .with_label_values(&[processor_name])
+            .set(0);
+        info!(
+            processor_name = processor_name,
+            service_type = PROCESSOR_SERVICE_TYPE,
+            channel_size = txn_sender.len(),
+            duration_in_secs = throttle_time.elapsed().as_secs_f64(),
+            "[Parser] Resuming GRPC fetching"
+        );
+        true
+    }
+}
@@ -3,8 +3,9 @@
 
 use once_cell::sync::Lazy;
 use prometheus::{
-    register_gauge_vec, register_int_counter, register_int_counter_vec, register_int_gauge_vec,
-    GaugeVec, IntCounter, IntCounterVec, IntGaugeVec,
+    exponential_buckets, register_gauge_vec, register_histogram_vec, register_int_counter,
+    register_int_counter_vec, register_int_gauge_vec, GaugeVec, HistogramVec, IntCounter,
+    IntCounterVec, IntGaugeVec,
 };
 
 pub enum ProcessorStep {
@@ -255,6 +256,16 @@ pub static GRPC_LATENCY_BY_PROCESSOR_IN_SECS: Lazy<GaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Time the fetcher spent waiting on the GRPC stream for the latest batch.
+pub static GRPC_BATCH_FETCH_LATENCY_IN_SECS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "indexer_processor_grpc_batch_fetch_latency_in_secs",
+        "Time spent waiting on the GRPC stream for a batch of transactions",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
 /// Processor unknown type count.
 pub static PROCESSOR_UNKNOWN_TYPE_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -265,6 +276,60 @@ pub static PROCESSOR_UNKNOWN_TYPE_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of chain values that did not fit into an i64 column and were saturated.
+pub static PROCESSOR_NUMERIC_OVERFLOW_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_numeric_overflow_count",
+        "Number of chain values saturated because they overflow an i64 column",
+        &["field_name"]
+    )
+    .unwrap()
+});
+
+/// Number of rows a dry run parsed but did not insert, per model.
+pub static DRY_RUN_SKIPPED_ROWS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_dry_run_skipped_rows_count",
+        "Number of rows parsed but not inserted because of dry run",
+        &["model_name"]
+    )
+    .unwrap()
+});
+
+/// Time from the chain timestamp of a transaction until its batch is committed to the DB.
+/// `first` is the oldest transaction of the batch (worst case), `last` the newest one. Not
+/// recorded for parquet processors, which don't write to the DB.
+pub static PROCESSOR_CHAIN_TO_COMMIT_LATENCY_IN_SECS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "indexer_processor_chain_to_commit_latency_in_secs",
+        "Latency from transaction chain timestamp to DB commit",
+        &["processor_name", "transaction_in_batch"],
+        // 100ms up to ~27 minutes
+        exponential_buckets(0.1, 2.0, 15).unwrap()
+    )
+    .unwrap()
+});
+
+/// Whether the fetcher is currently holding back because the DB is falling behind.
+pub static FETCHER_THROTTLED: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_processor_fetcher_throttled",
+        "Whether GRPC fetching is throttled because of DB backpressure",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Chunk size currently used for inserts of each model when chunk size auto tuning is enabled.
+pub static TUNED_CHUNK_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_processor_tuned_chunk_size",
+        "Auto tuned insert chunk size per model",
+        &["model_name"]
+    )
+    .unwrap()
+});
+
 /// Parquet struct size
 pub static PARQUET_STRUCT_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!("indexer_parquet_struct_size", "Parquet struct size", &[
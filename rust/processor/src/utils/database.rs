@@ -4,9 +4,13 @@
 //! Database-related functions
 #![allow(clippy::extra_unused_lifetimes)]
 
-use crate::utils::util::remove_null_bytes;
+use crate::utils::{
+    counters::{DRY_RUN_SKIPPED_ROWS_COUNT, TUNED_CHUNK_SIZE},
+    util::remove_null_bytes,
+};
 use ahash::AHashMap;
 use diesel::{
+    migration::{Migration, MigrationSource},
     query_builder::{AstPass, Query, QueryFragment},
     ConnectionResult, QueryResult,
 };
@@ -19,7 +23,15 @@ use diesel_async::{
 };
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use futures_util::{future::BoxFuture, FutureExt};
-use std::sync::Arc;
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
 pub type Backend = diesel::pg::Pg;
 
@@ -32,6 +44,93 @@ pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("src/db/postgres/mi
 
 pub const DEFAULT_MAX_POOL_SIZE: u32 = 150;
 
+/// When set, every write below is skipped and only counted so a processor can be validated
+/// against a new deployment without touching the DB. Set once at startup by the worker.
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+pub fn enable_dry_run() {
+    DRY_RUN.store(true, Ordering::Relaxed);
+}
+
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// Chunks taking longer than this get smaller, chunks well under it grow back towards the
+/// configured size. Chunks of a batch run concurrently so this is compared to the slowest one.
+pub const TARGET_CHUNK_INSERT_LATENCY: Duration = Duration::from_millis(500);
+pub const MIN_TUNED_CHUNK_SIZE: usize = 100;
+
+static CHUNK_SIZE_AUTO_TUNING: AtomicBool = AtomicBool::new(false);
+
+/// Current chunk size per model, only populated when auto tuning is enabled
+static TUNED_CHUNK_SIZES: Lazy<Mutex<AHashMap<&'static str, usize>>> =
+    Lazy::new(|| Mutex::new(AHashMap::new()));
+
+pub fn enable_chunk_size_auto_tuning() {
+    CHUNK_SIZE_AUTO_TUNING.store(true, Ordering::Relaxed);
+}
+
+fn model_name<T>() -> &'static str {
+    // e.g. `Event` rather than the full module path
+    let type_name = std::any::type_name::<T>();
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
+/// Returns the chunk size to use for the next insert of this model. The configured size (which
+/// defaults to the diesel parameter limit) is always the upper bound.
+fn tuned_chunk_size(model_name: &'static str, configured_chunk_size: usize) -> usize {
+    TUNED_CHUNK_SIZES
+        .lock()
+        .unwrap()
+        .get(model_name)
+        .copied()
+        .unwrap_or(configured_chunk_size)
+        .min(configured_chunk_size)
+}
+
+/// Halves the chunk size on errors or slow chunks and grows it by a quarter when chunks are
+/// comfortably fast, staying within `[MIN_TUNED_CHUNK_SIZE, configured_chunk_size]`.
+pub fn next_chunk_size(
+    current_chunk_size: usize,
+    configured_chunk_size: usize,
+    slowest_chunk_latency: Duration,
+    failed: bool,
+) -> usize {
+    let min_chunk_size = MIN_TUNED_CHUNK_SIZE.min(configured_chunk_size);
+    if failed || slowest_chunk_latency > TARGET_CHUNK_INSERT_LATENCY {
+        (current_chunk_size / 2).max(min_chunk_size)
+    } else if slowest_chunk_latency < TARGET_CHUNK_INSERT_LATENCY / 2 {
+        (current_chunk_size + current_chunk_size / 4)
+            .max(current_chunk_size + 1)
+            .min(configured_chunk_size)
+    } else {
+        current_chunk_size
+    }
+}
+
+fn record_chunk_latency(
+    model_name: &'static str,
+    chunk_size: usize,
+    configured_chunk_size: usize,
+    slowest_chunk_latency: Duration,
+    failed: bool,
+) {
+    let new_chunk_size = next_chunk_size(
+        chunk_size,
+        configured_chunk_size,
+        slowest_chunk_latency,
+        failed,
+    );
+    TUNED_CHUNK_SIZES
+        .lock()
+        .unwrap()
+        .insert(model_name, new_chunk_size);
+    TUNED_CHUNK_SIZE
+        .with_label_values(&[model_name])
+        .set(new_chunk_size as i64);
+}
+
 #[derive(QueryId)]
 /// Using this will append a where clause at the end of the string upsert function, e.g.
 /// INSERT INTO ... ON CONFLICT DO UPDATE SET ... WHERE "transaction_version" = excluded."transaction_version"
@@ -103,6 +202,11 @@ fn parse_and_clean_db_url(url: &str) -> (String, Option<String>) {
     (db_url.to_string(), cert_path)
 }
 
+/// Pool size used by `new_db_pool` when none is configured.
+pub fn max_pool_size_or_default(max_pool_size: Option<u32>) -> u32 {
+    max_pool_size.unwrap_or(DEFAULT_MAX_POOL_SIZE)
+}
+
 pub async fn new_db_pool(
     database_url: &str,
     max_pool_size: Option<u32>,
@@ -117,7 +221,7 @@ pub async fn new_db_pool(
         AsyncDieselConnectionManager::<MyDbConnection>::new(database_url)
     };
     let pool = Pool::builder()
-        .max_size(max_pool_size.unwrap_or(DEFAULT_MAX_POOL_SIZE))
+        .max_size(max_pool_size_or_default(max_pool_size))
         .build(config)
         .await?;
     Ok(Arc::new(pool))
@@ -133,6 +237,25 @@ where
     U: QueryFragment<Backend> + diesel::query_builder::QueryId + Send + 'static,
     T: serde::Serialize + for<'de> serde::Deserialize<'de> + Clone + Send + 'static,
 {
+    let model_name = model_name::<T>();
+    if is_dry_run() {
+        tracing::debug!(
+            model_name,
+            num_rows = items_to_insert.len(),
+            "[Parser] Dry run, skipping insert"
+        );
+        DRY_RUN_SKIPPED_ROWS_COUNT
+            .with_label_values(&[model_name])
+            .inc_by(items_to_insert.len() as u64);
+        return Ok(());
+    }
+    let auto_tune = CHUNK_SIZE_AUTO_TUNING.load(Ordering::Relaxed) && !items_to_insert.is_empty();
+    let configured_chunk_size = chunk_size;
+    let chunk_size = if auto_tune {
+        tuned_chunk_size(model_name, configured_chunk_size)
+    } else {
+        configured_chunk_size
+    };
     let tasks = items_to_insert
         .chunks(chunk_size)
         .map(|chunk| {
@@ -149,8 +272,26 @@ where
     let results = futures_util::future::try_join_all(tasks)
         .await
         .expect("Task panicked executing in chunks");
+    if auto_tune {
+        // Chunks run concurrently, so tune on the slowest one. This only counts query time,
+        // waiting for a pooled connection would otherwise shrink chunks when the pool is busy,
+        // which only adds more chunks competing for connections.
+        let slowest_chunk_latency = results
+            .iter()
+            .filter_map(|res| res.as_ref().ok())
+            .max()
+            .copied()
+            .unwrap_or_default();
+        record_chunk_latency(
+            model_name,
+            chunk_size,
+            configured_chunk_size,
+            slowest_chunk_latency,
+            results.iter().any(|res| res.is_err()),
+        );
+    }
     for res in results {
-        res?
+        res?;
     }
 
     Ok(())
@@ -159,8 +300,23 @@ where
 pub async fn execute_with_better_error<U>(
     pool: ArcDbPool,
     query: U,
-    mut additional_where_clause: Option<&'static str>,
+    additional_where_clause: Option<&'static str>,
 ) -> QueryResult<usize>
+where
+    U: QueryFragment<Backend> + diesel::query_builder::QueryId + Send,
+{
+    execute_with_better_error_timed(pool, query, additional_where_clause)
+        .await
+        .map(|(rows, _)| rows)
+}
+
+/// Same as `execute_with_better_error`, but also returns how long the query itself took,
+/// not counting the wait for a connection from the pool.
+async fn execute_with_better_error_timed<U>(
+    pool: ArcDbPool,
+    query: U,
+    mut additional_where_clause: Option<&'static str>,
+) -> QueryResult<(usize, Duration)>
 where
     U: QueryFragment<Backend> + diesel::query_builder::QueryId + Send,
 {
@@ -175,6 +331,10 @@ where
         where_clause: additional_where_clause,
     };
     let debug_string = diesel::debug_query::<Backend, _>(&final_query).to_string();
+    if is_dry_run() {
+        tracing::debug!("Dry run, skipping query: {:?}", debug_string);
+        return Ok((0, Duration::ZERO));
+    }
     tracing::debug!("Executing query: {:?}", debug_string);
     let conn = &mut pool.get().await.map_err(|e| {
         tracing::warn!("Error getting connection from pool: {:?}", e);
@@ -183,11 +343,12 @@ where
             Box::new(e.to_string()),
         )
     })?;
+    let query_start_time = Instant::now();
     let res = final_query.execute(conn).await;
     if let Err(ref e) = res {
         tracing::warn!("Error running query: {:?}\n{:?}", e, debug_string);
     }
-    res
+    res.map(|rows| (rows, query_start_time.elapsed()))
 }
 
 /// Returns the entry for the config hashmap, or the default field count for the insert
@@ -222,6 +383,10 @@ where
         where_clause: additional_where_clause,
     };
     let debug_string = diesel::debug_query::<Backend, _>(&final_query).to_string();
+    if is_dry_run() {
+        tracing::debug!("Dry run, skipping query: {:?}", debug_string);
+        return Ok(0);
+    }
     tracing::debug!("Executing query: {:?}", debug_string);
     let res = final_query.execute(conn).await;
     if let Err(ref e) = res {
@@ -236,32 +401,69 @@ async fn execute_or_retry_cleaned<U, T>(
     items: Vec<T>,
     query: U,
     additional_where_clause: Option<&'static str>,
-) -> Result<(), diesel::result::Error>
+) -> Result<Duration, diesel::result::Error>
 where
     U: QueryFragment<Backend> + diesel::query_builder::QueryId + Send,
     T: serde::Serialize + for<'de> serde::Deserialize<'de> + Clone,
 {
-    match execute_with_better_error(conn.clone(), query, additional_where_clause).await {
-        Ok(_) => {},
+    // Returns the query time of the attempt that succeeded
+    match execute_with_better_error_timed(conn.clone(), query, additional_where_clause).await {
+        Ok((_, query_duration)) => Ok(query_duration),
         Err(_) => {
             let cleaned_items = clean_data_for_db(items, true);
             let (cleaned_query, additional_where_clause) = build_query(cleaned_items);
-            match execute_with_better_error(conn.clone(), cleaned_query, additional_where_clause)
+            execute_with_better_error_timed(conn.clone(), cleaned_query, additional_where_clause)
                 .await
-            {
-                Ok(_) => {},
-                Err(e) => {
-                    return Err(e);
-                },
-            }
+                .map(|(_, query_duration)| query_duration)
         },
     }
-    Ok(())
 }
 
-pub fn run_pending_migrations<DB: diesel::backend::Backend>(conn: &mut impl MigrationHarness<DB>) {
-    conn.run_pending_migrations(MIGRATIONS)
-        .expect("[Parser] Migrations failed!");
+/// Runs the embedded migrations if `auto_migrate` is set, then makes sure the DB schema matches
+/// what this binary expects. Panics if any embedded migration hasn't been applied, since writes
+/// against an older schema would otherwise fail (or silently drop columns) at upsert time.
+pub fn run_pending_migrations<DB: diesel::backend::Backend>(
+    conn: &mut impl MigrationHarness<DB>,
+    auto_migrate: bool,
+) {
+    if auto_migrate {
+        conn.run_pending_migrations(MIGRATIONS)
+            .expect("[Parser] Migrations failed!");
+    }
+    check_schema_version(conn);
+}
+
+fn check_schema_version<DB: diesel::backend::Backend>(conn: &mut impl MigrationHarness<DB>) {
+    let pending = conn
+        .pending_migrations(MIGRATIONS)
+        .expect("[Parser] Failed to read pending migrations");
+    if let Some(first_pending) = pending.first() {
+        panic!(
+            "[Parser] DB schema is behind this binary, {} migrations pending starting at {}. Run the migrations or set auto_migrate",
+            pending.len(),
+            first_pending.name()
+        );
+    }
+
+    // A newer schema is usually additive, so only warn when the DB is ahead of the binary
+    let known_versions = MigrationSource::<DB>::migrations(&MIGRATIONS)
+        .expect("[Parser] Failed to read embedded migrations")
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .collect::<HashSet<_>>();
+    let unknown_versions = conn
+        .applied_migrations()
+        .expect("[Parser] Failed to read applied migrations")
+        .iter()
+        .map(ToString::to_string)
+        .filter(|version| !known_versions.contains(version))
+        .collect::<Vec<_>>();
+    if !unknown_versions.is_empty() {
+        tracing::warn!(
+            unknown_versions = ?unknown_versions,
+            "[Parser] DB has migrations applied that this binary doesn't know about"
+        );
+    }
 }
 
 /// Section below is required to modify the query.
@@ -283,3 +485,29 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_chunk_size() {
+        let fast = TARGET_CHUNK_INSERT_LATENCY / 4;
+        let slow = TARGET_CHUNK_INSERT_LATENCY * 2;
+        let on_target = TARGET_CHUNK_INSERT_LATENCY * 3 / 4;
+
+        assert_eq!(next_chunk_size(1000, 2000, slow, false), 500);
+        assert_eq!(next_chunk_size(1000, 2000, fast, true), 500);
+        assert_eq!(next_chunk_size(1000, 2000, on_target, false), 1000);
+        assert_eq!(next_chunk_size(1000, 2000, fast, false), 1250);
+        // Never grows past the configured size or shrinks below the minimum
+        assert_eq!(next_chunk_size(1800, 2000, fast, false), 2000);
+        assert_eq!(
+            next_chunk_size(150, 2000, slow, false),
+            MIN_TUNED_CHUNK_SIZE
+        );
+        // Small tables configured below the minimum keep their configured size
+        assert_eq!(next_chunk_size(10, 10, slow, false), 10);
+        assert_eq!(next_chunk_size(2, 10, fast, false), 3);
+    }
+}
@@ -1,6 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod backpressure;
 pub mod counters;
 pub mod database;
+pub mod stall_watchdog;
+pub mod starting_version;
 pub mod util;
@@ -0,0 +1,130 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Watchdog that fires a webhook when the processor stops committing batches.
+
+use crate::{config::StallAlertConfig, worker::PROCESSOR_SERVICE_TYPE};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+// Upper bound on how often the watchdog checks for stalls
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+// A webhook that never answers must not block later alerts
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tracks when a processor task last committed a batch. Cloned into every processor task.
+#[derive(Clone, Debug)]
+pub struct CommitTracker(Arc<Mutex<Instant>>);
+
+impl Default for CommitTracker {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+}
+
+impl CommitTracker {
+    pub fn record_commit(&self) {
+        *self.0.lock().unwrap() = Instant::now();
+    }
+
+    pub fn since_last_commit(&self) -> Duration {
+        self.0.lock().unwrap().elapsed()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Alert {
+    Stalled,
+    Recovered,
+}
+
+/// Only alert on transitions so a long stall results in a single alert plus one when it
+/// recovers, rather than one per check.
+fn next_alert(alerting: bool, stalled: bool) -> Option<Alert> {
+    match (alerting, stalled) {
+        (false, true) => Some(Alert::Stalled),
+        (true, false) => Some(Alert::Recovered),
+        _ => None,
+    }
+}
+
+/// Checks the tracker forever and posts a Slack compatible `{"text": ...}` payload to the
+/// configured webhook when no batch has been committed for `stall_threshold_secs`, and again
+/// once batches are committed again. Time spent paused through the admin endpoint doesn't count.
+pub async fn run_stall_watchdog(
+    config: StallAlertConfig,
+    processor_name: &'static str,
+    commit_tracker: CommitTracker,
+) {
+    let stall_threshold = Duration::from_secs(config.stall_threshold_secs);
+    let check_interval = (stall_threshold / 4).clamp(Duration::from_secs(1), MAX_CHECK_INTERVAL);
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .expect("[Parser] Failed to build stall alert webhook client");
+    let mut alerting = false;
+    info!(
+        processor_name = processor_name,
+        service_type = PROCESSOR_SERVICE_TYPE,
+        stall_threshold_secs = config.stall_threshold_secs,
+        "[Parser] Starting stall watchdog"
+    );
+
+    loop {
+        tokio::time::sleep(check_interval).await;
+        if server_framework::is_indexing_paused() {
+            commit_tracker.record_commit();
+            continue;
+        }
+        let since_last_commit = commit_tracker.since_last_commit();
+        let text = match next_alert(alerting, since_last_commit > stall_threshold) {
+            Some(Alert::Stalled) => format!(
+                "{} has not committed a batch in {}s",
+                processor_name,
+                since_last_commit.as_secs()
+            ),
+            Some(Alert::Recovered) => format!("{} is committing batches again", processor_name),
+            None => continue,
+        };
+        alerting = !alerting;
+
+        warn!(
+            processor_name = processor_name,
+            service_type = PROCESSOR_SERVICE_TYPE,
+            since_last_commit_in_secs = since_last_commit.as_secs_f64(),
+            "[Parser] {}",
+            text
+        );
+        let res = client
+            .post(config.webhook_url.clone())
+            .json(&serde_json::json!({ "text": text }))
+            .send()
+            .await
+            .and_then(|res| res.error_for_status());
+        if let Err(e) = res {
+            // Keep going: a broken webhook shouldn't take the processor down
+            warn!(
+                processor_name = processor_name,
+                service_type = PROCESSOR_SERVICE_TYPE,
+                error = ?e,
+                "[Parser] Failed to send stall alert"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_alert_only_on_transitions() {
+        assert_eq!(next_alert(false, false), None);
+        assert_eq!(next_alert(false, true), Some(Alert::Stalled));
+        assert_eq!(next_alert(true, true), None);
+        assert_eq!(next_alert(true, false), Some(Alert::Recovered));
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers to pick a starting version when neither the config nor the DB has one.
+
+use crate::utils::util::standardize_address;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::time::Duration;
+use url::Url;
+
+const PUBLISH_PACKAGE_ENTRY_FUNCTION: &str = "0x1::code::publish_package_txn";
+// Max page size supported by the fullnode for account transactions
+const ACCOUNT_TRANSACTIONS_PAGE_SIZE: u64 = 100;
+// Fail startup instead of hanging on an unresponsive fullnode
+const FULLNODE_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The subset of a REST API transaction we need to find the publish transaction.
+#[derive(Debug, Deserialize)]
+struct AccountTransaction {
+    version: String,
+    #[serde(default)]
+    success: bool,
+    #[serde(default)]
+    payload: serde_json::Value,
+}
+
+impl AccountTransaction {
+    fn is_successful_package_publish(&self) -> bool {
+        self.success
+            && self.payload.get("function").and_then(|f| f.as_str())
+                == Some(PUBLISH_PACKAGE_ENTRY_FUNCTION)
+    }
+}
+
+/// Returns the version of the first successful `0x1::code::publish_package_txn` sent by
+/// `module_address`, paging through the account's transactions on the fullnode.
+/// Note that this only finds packages published directly by the account, not ones deployed
+/// through objects or resource accounts created by another sender.
+pub async fn get_module_publish_version(
+    fullnode_rest_url: &Url,
+    module_address: &str,
+) -> Result<u64> {
+    let client = reqwest::Client::builder()
+        .timeout(FULLNODE_REQUEST_TIMEOUT)
+        .build()
+        .context("Failed to build fullnode REST client")?;
+    let url = format!(
+        "{}/accounts/{}/transactions",
+        fullnode_rest_url.as_str().trim_end_matches('/'),
+        standardize_address(module_address)
+    );
+    let mut start = 0;
+    loop {
+        let transactions = client
+            .get(&url)
+            .query(&[("start", start), ("limit", ACCOUNT_TRANSACTIONS_PAGE_SIZE)])
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .with_context(|| format!("Failed to fetch account transactions from {}", url))?
+            .json::<Vec<AccountTransaction>>()
+            .await
+            .context("Failed to parse account transactions")?;
+        if transactions.is_empty() {
+            anyhow::bail!(
+                "No package publish transaction found for module address {}",
+                module_address
+            );
+        }
+        if let Some(txn) = transactions
+            .iter()
+            .find(|txn| txn.is_successful_package_publish())
+        {
+            return txn
+                .version
+                .parse::<u64>()
+                .with_context(|| format!("Invalid transaction version {}", txn.version));
+        }
+        start += transactions.len() as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_successful_package_publish() {
+        let txns: Vec<AccountTransaction> = serde_json::from_str(
+            r#"[
+                {"version": "10", "success": true, "payload": {"function": "0x1::aptos_account::transfer"}},
+                {"version": "11", "success": false, "payload": {"function": "0x1::code::publish_package_txn"}},
+                {"version": "12", "success": true, "payload": {"function": "0x1::code::publish_package_txn"}}
+            ]"#,
+        )
+        .unwrap();
+        let publish_txn = txns
+            .iter()
+            .find(|txn| txn.is_successful_package_publish())
+            .unwrap();
+        assert_eq!(publish_txn.version, "12");
+    }
+}
@@ -3,7 +3,7 @@
 
 use crate::{
     db::common::models::property_map::{PropertyMap, TokenObjectPropertyMap},
-    utils::counters::PROCESSOR_UNKNOWN_TYPE_COUNT,
+    utils::counters::{PROCESSOR_NUMERIC_OVERFLOW_COUNT, PROCESSOR_UNKNOWN_TYPE_COUNT},
 };
 use aptos_protos::{
     transaction::v1::{
@@ -96,6 +96,24 @@ pub fn u64_to_bigdecimal(val: u64) -> BigDecimal {
     BigDecimal::from(val)
 }
 
+/// Converts a u64 chain value into an i64 column value. Values above i64::MAX can't be
+/// represented in postgres BIGINT, so rather than wrapping around to a negative number
+/// we saturate at i64::MAX, log a warning and bump a counter so the batch still goes through.
+/// Use a BigDecimal column instead for fields where the full range matters.
+pub fn u64_to_i64_saturating(val: u64, field_name: &'static str) -> i64 {
+    i64::try_from(val).unwrap_or_else(|_| {
+        tracing::warn!(
+            field_name = field_name,
+            value = val,
+            "Value overflows i64, saturating to i64::MAX"
+        );
+        PROCESSOR_NUMERIC_OVERFLOW_COUNT
+            .with_label_values(&[field_name])
+            .inc();
+        i64::MAX
+    })
+}
+
 pub fn bigdecimal_to_u64(val: &BigDecimal) -> u64 {
     val.to_u64().expect("Unable to convert big decimal to u64")
 }
@@ -107,6 +125,28 @@ pub fn ensure_not_negative(val: BigDecimal) -> BigDecimal {
     val
 }
 
+/// Standardizes the address inside an entry function id, e.g. 0x1::coin::transfer becomes
+/// 0x0000000000000000000000000000000000000000000000000000000000000001::coin::transfer.
+/// Upstream formats addresses as short hex literals, which makes equality filters unreliable.
+pub fn standardize_entry_function_id_str(entry_function_id_str: &str) -> String {
+    match entry_function_id_str.split_once("::") {
+        Some((address, rest)) => format!("{}::{}", standardize_address(address), rest),
+        None => entry_function_id_str.to_string(),
+    }
+}
+
+/// Standardizes an entry function id as returned by `get_entry_function_from_user_request`.
+/// Every table storing `entry_function_id_str` writes this form, and the id as received goes
+/// into `raw_entry_function_id_str`.
+pub fn get_standardized_entry_function(entry_function_id_str: &Option<String>) -> Option<String> {
+    entry_function_id_str.as_deref().map(|s| {
+        truncate_str(
+            &standardize_entry_function_id_str(s),
+            MAX_ENTRY_FUNCTION_LENGTH,
+        )
+    })
+}
+
 pub fn get_entry_function_from_user_request(
     user_request: &UserTransactionRequest,
 ) -> Option<String> {
@@ -526,6 +566,35 @@ mod tests {
         assert_eq!(ts3.and_utc().timestamp(), 1659386386);
     }
 
+    #[test]
+    fn test_standardize_entry_function_id_str() {
+        assert_eq!(
+            standardize_entry_function_id_str("0x1::coin::transfer"),
+            "0x0000000000000000000000000000000000000000000000000000000000000001::coin::transfer"
+        );
+        let standardized =
+            "0x0abc000000000000000000000000000000000000000000000000000000000001::m::f";
+        assert_eq!(
+            standardize_entry_function_id_str(
+                "0xabc000000000000000000000000000000000000000000000000000000000001::m::f"
+            ),
+            standardized
+        );
+        assert_eq!(
+            standardize_entry_function_id_str(standardized),
+            standardized
+        );
+        assert_eq!(standardize_entry_function_id_str(""), "");
+    }
+
+    #[test]
+    fn test_u64_to_i64_saturating() {
+        assert_eq!(u64_to_i64_saturating(0, "test"), 0);
+        assert_eq!(u64_to_i64_saturating(i64::MAX as u64, "test"), i64::MAX);
+        assert_eq!(u64_to_i64_saturating(i64::MAX as u64 + 1, "test"), i64::MAX);
+        assert_eq!(u64_to_i64_saturating(u64::MAX, "test"), i64::MAX);
+    }
+
     #[test]
     fn test_deserialize_string_from_bcs() {
         let test_struct = TypeInfoMock {
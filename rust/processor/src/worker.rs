@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    config::IndexerGrpcHttp2Config,
+    config::{
+        BackpressureConfig, IndexerGrpcHttp2Config, StallAlertConfig,
+        StartingVersionAutoDetectConfig,
+    },
     db::common::models::{ledger_info::LedgerInfo, processor_status::ProcessorStatusQuery},
     gap_detectors::{create_gap_detector_status_tracker_loop, ProcessingResult},
     grpc_stream::TransactionsPBResponse,
@@ -21,18 +24,22 @@ use crate::{
     schema::ledger_infos,
     transaction_filter::TransactionFilter,
     utils::{
+        backpressure::Backpressure,
         counters::{
             ProcessorStep, GRPC_LATENCY_BY_PROCESSOR_IN_SECS, LATEST_PROCESSED_VERSION,
             NUM_TRANSACTIONS_PROCESSED_COUNT, PB_CHANNEL_FETCH_WAIT_TIME_SECS,
-            PROCESSED_BYTES_COUNT, PROCESSOR_DATA_PROCESSED_LATENCY_IN_SECS,
-            PROCESSOR_DATA_RECEIVED_LATENCY_IN_SECS, PROCESSOR_ERRORS_COUNT,
-            PROCESSOR_INVOCATIONS_COUNT, PROCESSOR_SUCCESSES_COUNT,
+            PROCESSED_BYTES_COUNT, PROCESSOR_CHAIN_TO_COMMIT_LATENCY_IN_SECS,
+            PROCESSOR_DATA_PROCESSED_LATENCY_IN_SECS, PROCESSOR_DATA_RECEIVED_LATENCY_IN_SECS,
+            PROCESSOR_ERRORS_COUNT, PROCESSOR_INVOCATIONS_COUNT, PROCESSOR_SUCCESSES_COUNT,
             SINGLE_BATCH_DB_INSERTION_TIME_IN_SECS, SINGLE_BATCH_PARSING_TIME_IN_SECS,
             SINGLE_BATCH_PROCESSING_TIME_IN_SECS, TRANSACTION_UNIX_TIMESTAMP,
         },
         database::{
-            execute_with_better_error_conn, new_db_pool, run_pending_migrations, ArcDbPool,
+            enable_chunk_size_auto_tuning, enable_dry_run, execute_with_better_error_conn,
+            is_dry_run, max_pool_size_or_default, new_db_pool, run_pending_migrations, ArcDbPool,
         },
+        stall_watchdog::{run_stall_watchdog, CommitTracker},
+        starting_version::get_module_publish_version,
         util::{time_diff_since_pb_timestamp_in_secs, timestamp_to_iso, timestamp_to_unixtime},
     },
 };
@@ -72,6 +79,7 @@ pub struct Worker {
     pub grpc_http2_config: IndexerGrpcHttp2Config,
     pub auth_token: String,
     pub starting_version: Option<u64>,
+    pub starting_version_auto_detect: Option<StartingVersionAutoDetectConfig>,
     pub ending_version: Option<u64>,
     pub number_concurrent_processing_tasks: usize,
     pub gap_detection_batch_size: u64,
@@ -83,6 +91,11 @@ pub struct Worker {
     pub transaction_filter: TransactionFilter,
     pub grpc_response_item_timeout_in_secs: u64,
     pub deprecated_tables: TableFlags,
+    pub dry_run: bool,
+    pub auto_migrate: bool,
+    pub enable_chunk_size_auto_tuning: bool,
+    pub stall_alert: Option<StallAlertConfig>,
+    pub backpressure: Option<Backpressure>,
 }
 
 impl Worker {
@@ -94,6 +107,7 @@ impl Worker {
         grpc_http2_config: IndexerGrpcHttp2Config,
         auth_token: String,
         starting_version: Option<u64>,
+        starting_version_auto_detect: Option<StartingVersionAutoDetectConfig>,
         ending_version: Option<u64>,
         number_concurrent_processing_tasks: Option<usize>,
         db_pool_size: Option<u32>,
@@ -106,6 +120,11 @@ impl Worker {
         transaction_filter: TransactionFilter,
         grpc_response_item_timeout_in_secs: u64,
         deprecated_tables: HashSet<String>,
+        dry_run: bool,
+        auto_migrate: bool,
+        enable_chunk_size_auto_tuning: bool,
+        stall_alert: Option<StallAlertConfig>,
+        backpressure_config: Option<BackpressureConfig>,
     ) -> Result<Self> {
         let processor_name = processor_config.name();
         info!(processor_name = processor_name, "[Parser] Kicking off");
@@ -124,6 +143,13 @@ impl Worker {
             "[Parser] Finish creating the connection pool"
         );
         let number_concurrent_processing_tasks = number_concurrent_processing_tasks.unwrap_or(10);
+        let backpressure = backpressure_config.map(|config| {
+            Backpressure::new(
+                config,
+                conn_pool.clone(),
+                max_pool_size_or_default(db_pool_size),
+            )
+        });
 
         let mut deprecated_tables_flags = TableFlags::empty();
         for table in deprecated_tables.iter() {
@@ -139,6 +165,7 @@ impl Worker {
             indexer_grpc_data_service_address,
             grpc_http2_config,
             starting_version,
+            starting_version_auto_detect,
             ending_version,
             auth_token,
             number_concurrent_processing_tasks,
@@ -151,6 +178,11 @@ impl Worker {
             transaction_filter,
             grpc_response_item_timeout_in_secs,
             deprecated_tables: deprecated_tables_flags,
+            dry_run,
+            auto_migrate,
+            enable_chunk_size_auto_tuning,
+            stall_alert,
+            backpressure,
         })
     }
 
@@ -162,9 +194,20 @@ impl Worker {
     /// 4. We will keep track of the last processed version and monitoring things like TPS
     pub async fn run(&mut self) {
         let processor_name = self.processor_config.name();
+        if self.dry_run {
+            // The DB is still read from (e.g. starting version, chain id), so it needs to
+            // have the schema already.
+            info!(
+                processor_name = processor_name,
+                service_type = PROCESSOR_SERVICE_TYPE,
+                "[Parser] Dry run enabled, only checking the schema and skipping all DB writes"
+            );
+            enable_dry_run();
+        }
         info!(
             processor_name = processor_name,
             service_type = PROCESSOR_SERVICE_TYPE,
+            auto_migrate = self.should_auto_migrate(),
             "[Parser] Running migrations"
         );
         let migration_time = std::time::Instant::now();
@@ -176,20 +219,44 @@ impl Worker {
             "[Parser] Finished migrations"
         );
 
+        if self.enable_chunk_size_auto_tuning {
+            enable_chunk_size_auto_tuning();
+        }
+
         let starting_version_from_db = self
             .get_start_version()
             .await
-            .expect("[Parser] Database error when getting starting version")
-            .unwrap_or_else(|| {
-                info!(
-                    processor_name = processor_name,
-                    service_type = PROCESSOR_SERVICE_TYPE,
-                    "[Parser] No starting version from db so starting from version 0"
-                );
-                0
-            });
-
-        let starting_version = self.starting_version.unwrap_or(starting_version_from_db);
+            .expect("[Parser] Database error when getting starting version");
+
+        let starting_version = match self.starting_version.or(starting_version_from_db) {
+            Some(version) => version,
+            None => match &self.starting_version_auto_detect {
+                Some(auto_detect_config) => {
+                    let version = get_module_publish_version(
+                        &auto_detect_config.fullnode_rest_url,
+                        &auto_detect_config.module_address,
+                    )
+                    .await
+                    .expect("[Parser] Failed to auto-detect starting version");
+                    info!(
+                        processor_name = processor_name,
+                        service_type = PROCESSOR_SERVICE_TYPE,
+                        module_address = auto_detect_config.module_address.as_str(),
+                        start_version = version,
+                        "[Parser] No starting version from db so starting from module publish version"
+                    );
+                    version
+                },
+                None => {
+                    info!(
+                        processor_name = processor_name,
+                        service_type = PROCESSOR_SERVICE_TYPE,
+                        "[Parser] No starting version from db so starting from version 0"
+                    );
+                    0
+                },
+            },
+        };
 
         info!(
             processor_name = processor_name,
@@ -238,6 +305,7 @@ impl Worker {
         let transaction_filter = self.transaction_filter.clone();
         let grpc_response_item_timeout =
             std::time::Duration::from_secs(self.grpc_response_item_timeout_in_secs);
+        let backpressure = self.backpressure.clone();
         let fetcher_task = tokio::spawn(async move {
             info!(
                 processor_name = processor_name,
@@ -260,6 +328,7 @@ impl Worker {
                 processor_name.to_string(),
                 transaction_filter,
                 pb_channel_txn_chunk_size,
+                backpressure,
             )
             .await
         });
@@ -325,10 +394,24 @@ impl Worker {
             "[Parser] Spawning concurrent parallel processor tasks",
         );
 
+        let commit_tracker = CommitTracker::default();
+        // Nothing gets committed in a dry run, so there's nothing to watch
+        if let Some(stall_alert) = self.stall_alert.clone().filter(|_| !self.dry_run) {
+            let commit_tracker = commit_tracker.clone();
+            tokio::spawn(async move {
+                run_stall_watchdog(stall_alert, processor_name, commit_tracker).await;
+            });
+        }
+
         let mut processor_tasks = vec![fetcher_task];
         for task_index in 0..concurrent_tasks {
             let join_handle: JoinHandle<()> = self
-                .launch_processor_task(task_index, receiver.clone(), gap_detector_sender.clone())
+                .launch_processor_task(
+                    task_index,
+                    receiver.clone(),
+                    gap_detector_sender.clone(),
+                    commit_tracker.clone(),
+                )
                 .await;
             processor_tasks.push(join_handle);
         }
@@ -352,11 +435,13 @@ impl Worker {
         task_index: usize,
         receiver: kanal::AsyncReceiver<TransactionsPBResponse>,
         gap_detector_sender: Option<AsyncSender<ProcessingResult>>,
+        commit_tracker: CommitTracker,
     ) -> JoinHandle<()> {
         let processor_name = self.processor_config.name();
         let stream_address = self.indexer_grpc_data_service_address.to_string();
         let receiver_clone = receiver.clone();
         let auth_token = self.auth_token.clone();
+        let backpressure = self.backpressure.clone();
 
         // Build the processor based on the config.
         let processor = build_processor(
@@ -392,6 +477,8 @@ impl Worker {
                     // Fetched transactions from channel
                     Ok(transactions_pb) => {
                         let size_in_bytes = transactions_pb.size_in_bytes as f64;
+                        let connection_id = transactions_pb.connection_id.clone();
+                        let fetch_duration_in_secs = transactions_pb.fetch_duration_in_secs;
                         let first_txn_version = transactions_pb
                             .transactions
                             .first()
@@ -426,12 +513,16 @@ impl Worker {
                         debug!(
                             processor_name = processor_name,
                             service_type = PROCESSOR_SERVICE_TYPE,
+                            stream_address = stream_address.as_str(),
+                            connection_id = connection_id.as_str(),
+                            chain_id = transactions_pb.chain_id,
                             start_version = batch_first_txn_version,
                             end_version = batch_last_txn_version,
                             num_of_transactions =
                                 (batch_last_txn_version - batch_first_txn_version) as i64 + 1,
                             size_in_bytes,
                             task_index,
+                            grpc_fetch_duration_in_secs = fetch_duration_in_secs,
                             duration_in_secs = txn_channel_fetch_latency_sec,
                             tps = (batch_last_txn_version as f64 - batch_first_txn_version as f64)
                                 / txn_channel_fetch_latency_sec,
@@ -474,6 +565,9 @@ impl Worker {
                                 PROCESSOR_SUCCESSES_COUNT
                                     .with_label_values(&[processor_name])
                                     .inc();
+                                if !is_dry_run() {
+                                    commit_tracker.record_commit();
+                                }
                                 versions
                             },
                             Err(e) => {
@@ -497,6 +591,11 @@ impl Worker {
                         match processing_result {
                             ProcessingResult::DefaultProcessingResult(processing_result) => {
                                 let processing_time = processing_time.elapsed().as_secs_f64();
+                                if let Some(backpressure) = &backpressure {
+                                    backpressure.record_db_insertion_latency(
+                                        processing_result.db_insertion_duration_in_secs,
+                                    );
+                                }
 
                                 // We've processed things: do some data and metrics
                                 ma.tick_now((last_txn_version - first_txn_version) + 1);
@@ -507,6 +606,10 @@ impl Worker {
                                 debug!(
                                     processor_name = processor_name,
                                     service_type = PROCESSOR_SERVICE_TYPE,
+                                    stream_address = stream_address.as_str(),
+                                    connection_id = connection_id.as_str(),
+                                    chain_id,
+                                    grpc_fetch_duration_in_secs = fetch_duration_in_secs,
                                     first_txn_version,
                                     batch_first_txn_version,
                                     last_txn_version,
@@ -622,7 +725,7 @@ impl Worker {
         info!("Running migrations: {:?}", self.postgres_connection_string);
         let mut conn =
             PgConnection::establish(&self.postgres_connection_string).expect("migrations failed!");
-        run_pending_migrations(&mut conn);
+        run_pending_migrations(&mut conn, self.should_auto_migrate());
     }
 
     // If the libpq feature isn't enabled, we use diesel async instead. This is used by
@@ -639,18 +742,25 @@ impl Worker {
             .dedicated_connection()
             .await
             .expect("[Parser] Failed to get connection");
+        let auto_migrate = self.should_auto_migrate();
         // We use spawn_blocking since run_pending_migrations is a blocking function.
         tokio::task::spawn_blocking(move || {
             // This lets us use the connection like a normal diesel connection. See more:
             // https://docs.rs/diesel-async/latest/diesel_async/async_connection_wrapper/type.AsyncConnectionWrapper.html
             let mut conn: AsyncConnectionWrapper<diesel_async::AsyncPgConnection> =
                 AsyncConnectionWrapper::from(conn);
-            run_pending_migrations(&mut conn);
+            run_pending_migrations(&mut conn, auto_migrate);
         })
         .await
         .expect("[Parser] Failed to run migrations");
     }
 
+    /// A dry run never applies migrations, but still checks that the schema is up to date so it
+    /// fails the same way the real run would.
+    fn should_auto_migrate(&self) -> bool {
+        self.auto_migrate && !self.dry_run
+    }
+
     /// Gets the start version for the processor. If not found, start from 0.
     pub async fn get_start_version(&self) -> Result<Option<u64>> {
         let mut conn = self.db_pool.get().await?;
@@ -764,6 +874,7 @@ pub async fn do_processor(
     }
 
     let txn_time = transactions_pb.start_txn_timestamp;
+    let end_txn_time = transactions_pb.end_txn_timestamp;
 
     if let Some(ref t) = txn_time {
         PROCESSOR_DATA_RECEIVED_LATENCY_IN_SECS
@@ -800,6 +911,24 @@ pub async fn do_processor(
             .set(time_diff_since_pb_timestamp_in_secs(t));
     }
 
+    // DB processors only return from process_transactions once the batch is written, so this is
+    // chain time to commit. Parquet processors return as soon as the batch is handed to the upload
+    // buffer, long before it reaches GCS, so they're left out. A dry run doesn't commit anything,
+    // so it would only skew the histogram.
+    if matches!(
+        processed_result,
+        Ok(ProcessingResult::DefaultProcessingResult(_))
+    ) && !is_dry_run()
+    {
+        for (transaction_in_batch, timestamp) in [("first", &txn_time), ("last", &end_txn_time)] {
+            if let Some(t) = timestamp {
+                PROCESSOR_CHAIN_TO_COMMIT_LATENCY_IN_SECS
+                    .with_label_values(&[processor_name, transaction_in_batch])
+                    .observe(time_diff_since_pb_timestamp_in_secs(t));
+            }
+        }
+    }
+
     processed_result
 }
 
@@ -9,11 +9,29 @@ use prometheus::{Encoder, TextEncoder};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 #[cfg(target_os = "linux")]
 use std::convert::Infallible;
-use std::{fs::File, io::Read, panic::PanicInfo, path::PathBuf, process};
+use std::{
+    fs::File,
+    io::Read,
+    panic::PanicInfo,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicBool, Ordering},
+};
 use tokio::runtime::Handle;
 use tracing::error;
 use tracing_subscriber::EnvFilter;
-use warp::{http::Response, Filter};
+use warp::{
+    http::{Method, Response, StatusCode},
+    Filter,
+};
+
+/// Set through the admin endpoints. Services check it between units of work, e.g. the processor
+/// stops fetching from GRPC while this is set, and keep their state so they can resume.
+static INDEXING_PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_indexing_paused() -> bool {
+    INDEXING_PAUSED.load(Ordering::Relaxed)
+}
 
 /// ServerArgs bootstraps a server with all common pieces. And then triggers the run method for
 /// the specific service.
@@ -43,9 +61,12 @@ where
     C: RunnableConfig,
 {
     let health_port = config.health_check_port;
+    let enable_profiling_endpoints = config.enable_profiling_endpoints;
+    let admin_token = config.admin_token.clone();
     // Start liveness and readiness probes.
     let task_handler = handle.spawn(async move {
-        register_probes_and_metrics_handler(health_port).await;
+        register_probes_and_metrics_handler(health_port, enable_profiling_endpoints, admin_token)
+            .await;
         anyhow::Ok(())
     });
     let main_task_handler = handle.spawn(async move { config.run().await });
@@ -63,6 +84,14 @@ where
 pub struct GenericConfig<T> {
     // Shared configuration among all services.
     pub health_check_port: u16,
+    // Expose the /profilez (CPU) and /heapz (jemalloc heap) endpoints on the health check
+    // port. Off by default since profiling adds overhead while it runs.
+    #[serde(default)]
+    pub enable_profiling_endpoints: bool,
+    // Expose /admin/pause, /admin/resume and /admin/status on the health check port, requiring
+    // an `Authorization: Bearer <admin_token>` header. Disabled when unset.
+    #[serde(default)]
+    pub admin_token: Option<String>,
 
     // Specific configuration for each service.
     pub server_config: T,
@@ -147,8 +176,175 @@ pub fn setup_logging() {
         .init();
 }
 
-/// Register readiness and liveness probes and set up metrics endpoint.
-async fn register_probes_and_metrics_handler(port: u16) {
+/// Query parameters for the /profilez endpoint.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Deserialize)]
+struct CpuProfilingParams {
+    #[serde(default = "CpuProfilingParams::default_seconds")]
+    seconds: u64,
+    #[serde(default = "CpuProfilingParams::default_frequency")]
+    frequency: i32,
+}
+
+#[cfg(target_os = "linux")]
+impl CpuProfilingParams {
+    const MAX_FREQUENCY: i32 = 1000;
+    // Without an admin token the endpoint is unauthenticated, so don't let a request hold the
+    // profiler for long
+    const MAX_SECONDS: u64 = 60;
+
+    const fn default_seconds() -> u64 {
+        10
+    }
+
+    const fn default_frequency() -> i32 {
+        99
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(1..=Self::MAX_SECONDS).contains(&self.seconds) {
+            return Err(format!(
+                "seconds must be between 1 and {}",
+                Self::MAX_SECONDS
+            ));
+        }
+        if !(1..=Self::MAX_FREQUENCY).contains(&self.frequency) {
+            return Err(format!(
+                "frequency must be between 1 and {}",
+                Self::MAX_FREQUENCY
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Dumps a jemalloc heap profile and returns its contents. This only works if the process
+/// was started with jemalloc profiling enabled, e.g. `MALLOC_CONF=prof:true`.
+#[cfg(target_os = "linux")]
+async fn dump_heap_profile() -> Result<Vec<u8>> {
+    tokio::task::spawn_blocking(|| {
+        let dir = tempfile::tempdir().context("Failed to create temp dir for heap profile")?;
+        let path = dir.path().join("heap.prof");
+        let path_cstr = std::ffi::CString::new(path.to_string_lossy().as_bytes())
+            .context("Heap profile path contains a null byte")?;
+        // SAFETY: prof.dump expects a null terminated path, which outlives this call.
+        unsafe { jemalloc_ctl::raw::write(b"prof.dump\0", path_cstr.as_ptr()) }.map_err(|e| {
+            anyhow::anyhow!("{e}. Is jemalloc profiling enabled (MALLOC_CONF=prof:true)?")
+        })?;
+        std::fs::read(&path).context("Failed to read heap profile")
+    })
+    .await
+    .context("Heap profile task panicked")?
+}
+
+/// Turns the output of a profiler into a response, or a 500 if profiling failed.
+#[cfg(target_os = "linux")]
+fn profile_response(
+    result: Result<Vec<u8>>,
+    content_type: &'static str,
+) -> warp::reply::WithStatus<Response<Vec<u8>>> {
+    let profiling_failed = |e: &dyn std::fmt::Debug| {
+        warp::reply::with_status(
+            Response::new(format!("Profiling failed: {e:?}.").as_bytes().to_vec()),
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    };
+    match result {
+        Ok(body) => {
+            let response = Response::builder()
+                .header("Content-Length", body.len())
+                .header("Content-Disposition", "inline")
+                .header("Content-Type", content_type)
+                .body(body);
+
+            match response {
+                Ok(res) => warp::reply::with_status(res, warp::http::StatusCode::OK),
+                Err(e) => profiling_failed(&e),
+            }
+        },
+        Err(e) => profiling_failed(&e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn unauthorized_profile_response() -> warp::reply::WithStatus<Response<Vec<u8>>> {
+    warp::reply::with_status(
+        Response::new(b"Unauthorized".to_vec()),
+        StatusCode::UNAUTHORIZED,
+    )
+}
+
+/// Compares without exiting early on the first differing byte, so the admin token can't be
+/// guessed byte by byte from response times. Only the length can leak.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Checks for an `Authorization: Bearer <admin_token>` header.
+fn is_authorized(admin_token: &str, authorization: Option<&str>) -> bool {
+    let expected_authorization = format!("Bearer {admin_token}");
+    authorization.is_some_and(|authorization| {
+        constant_time_eq(authorization.as_bytes(), expected_authorization.as_bytes())
+    })
+}
+
+/// Profiling endpoints need the same bearer token as `/admin/*` once an admin token is
+/// configured, and stay open otherwise.
+fn is_profiling_authorized(admin_token: Option<&str>, authorization: Option<&str>) -> bool {
+    match admin_token {
+        Some(admin_token) => is_authorized(admin_token, authorization),
+        None => true,
+    }
+}
+
+/// Handles `/admin/<action>`. Pausing and resuming need a POST, the status can be read with any
+/// method. Everything is a 404 if no admin token is configured.
+fn admin_response(
+    admin_token: Option<&str>,
+    authorization: Option<&str>,
+    method: &Method,
+    action: &str,
+) -> warp::reply::WithStatus<String> {
+    let Some(admin_token) = admin_token else {
+        return warp::reply::with_status("Not found".to_string(), StatusCode::NOT_FOUND);
+    };
+    if !is_authorized(admin_token, authorization) {
+        return warp::reply::with_status("Unauthorized".to_string(), StatusCode::UNAUTHORIZED);
+    }
+    match (action, method) {
+        ("pause", &Method::POST) => {
+            INDEXING_PAUSED.store(true, Ordering::Relaxed);
+            tracing::info!("Indexing paused through the admin endpoint");
+        },
+        ("resume", &Method::POST) => {
+            INDEXING_PAUSED.store(false, Ordering::Relaxed);
+            tracing::info!("Indexing resumed through the admin endpoint");
+        },
+        ("pause" | "resume", _) => {
+            return warp::reply::with_status(
+                "Method not allowed".to_string(),
+                StatusCode::METHOD_NOT_ALLOWED,
+            );
+        },
+        ("status", _) => {},
+        _ => return warp::reply::with_status("Not found".to_string(), StatusCode::NOT_FOUND),
+    }
+    let status = if is_indexing_paused() {
+        "paused"
+    } else {
+        "running"
+    };
+    warp::reply::with_status(status.to_string(), StatusCode::OK)
+}
+
+/// Register readiness and liveness probes and set up metrics endpoint. Profiling endpoints
+/// are only registered when `enable_profiling_endpoints` is set, and require the admin token
+/// if there is one.
+async fn register_probes_and_metrics_handler(
+    port: u16,
+    enable_profiling_endpoints: bool,
+    admin_token: Option<String>,
+) {
     let readiness = warp::path("readiness")
         .map(move || warp::reply::with_status("ready", warp::http::StatusCode::OK));
     let metrics_endpoint = warp::path("metrics").map(|| {
@@ -166,39 +362,75 @@ async fn register_probes_and_metrics_handler(port: u16) {
             .header("Content-Type", "text/plain")
             .body(encode_buffer)
     });
+    #[cfg(target_os = "linux")]
+    let profiling_admin_token = admin_token.clone();
+    let admin = warp::path("admin")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::method())
+        .and(warp::header::optional::<String>("authorization"))
+        .map(
+            move |action: String, method: Method, authorization: Option<String>| {
+                admin_response(
+                    admin_token.as_deref(),
+                    authorization.as_deref(),
+                    &method,
+                    &action,
+                )
+            },
+        );
 
-    if cfg!(target_os = "linux") {
+    if enable_profiling_endpoints && cfg!(target_os = "linux") {
         #[cfg(target_os = "linux")]
-        let profilez = warp::path("profilez").and_then(|| async move {
-            // TODO(grao): Consider make the parameters configurable.
-            Ok::<_, Infallible>(match start_cpu_profiling(10, 99, false).await {
-                Ok(body) => {
-                    let response = Response::builder()
-                        .header("Content-Length", body.len())
-                        .header("Content-Disposition", "inline")
-                        .header("Content-Type", "image/svg+xml")
-                        .body(body);
-
-                    match response {
-                        Ok(res) => warp::reply::with_status(res, warp::http::StatusCode::OK),
-                        Err(e) => warp::reply::with_status(
-                            Response::new(format!("Profiling failed: {e:?}.").as_bytes().to_vec()),
-                            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                        ),
+        let profiling_auth = warp::header::optional::<String>("authorization").map(
+            move |authorization: Option<String>| {
+                is_profiling_authorized(profiling_admin_token.as_deref(), authorization.as_deref())
+            },
+        );
+        #[cfg(target_os = "linux")]
+        let profilez = warp::path("profilez")
+            .and(profiling_auth.clone())
+            .and(warp::query::<CpuProfilingParams>())
+            .and_then(|authorized: bool, params: CpuProfilingParams| async move {
+                if !authorized {
+                    return Ok::<_, Infallible>(unauthorized_profile_response());
+                }
+                if let Err(e) = params.validate() {
+                    return Ok::<_, Infallible>(warp::reply::with_status(
+                        Response::new(e.into_bytes()),
+                        StatusCode::BAD_REQUEST,
+                    ));
+                }
+                let result = start_cpu_profiling(params.seconds, params.frequency, false)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("{e:?}"));
+                Ok::<_, Infallible>(profile_response(result, "image/svg+xml"))
+            });
+        #[cfg(target_os = "linux")]
+        let heapz =
+            warp::path("heapz")
+                .and(profiling_auth)
+                .and_then(|authorized: bool| async move {
+                    if !authorized {
+                        return Ok::<_, Infallible>(unauthorized_profile_response());
                     }
-                },
-                Err(e) => warp::reply::with_status(
-                    Response::new(format!("Profiling failed: {e:?}.").as_bytes().to_vec()),
-                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                ),
-            })
-        });
+                    Ok::<_, Infallible>(profile_response(
+                        dump_heap_profile().await,
+                        "application/octet-stream",
+                    ))
+                });
         #[cfg(target_os = "linux")]
-        warp::serve(readiness.or(metrics_endpoint).or(profilez))
-            .run(([0, 0, 0, 0], port))
-            .await;
+        warp::serve(
+            readiness
+                .or(metrics_endpoint)
+                .or(admin)
+                .or(profilez)
+                .or(heapz),
+        )
+        .run(([0, 0, 0, 0], port))
+        .await;
     } else {
-        warp::serve(readiness.or(metrics_endpoint))
+        warp::serve(readiness.or(metrics_endpoint).or(admin))
             .run(([0, 0, 0, 0], port))
             .await;
     }
@@ -246,10 +478,84 @@ mod tests {
 
         let config = load::<GenericConfig<TestConfig>>(&file_path).unwrap();
         assert_eq!(config.health_check_port, 12345);
+        assert!(!config.enable_profiling_endpoints);
+        assert!(config.admin_token.is_none());
         assert_eq!(config.server_config.test, 123);
         assert_eq!(config.server_config.test_name, "test");
     }
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cpu_profiling_params_bounds() {
+        let params = |seconds, frequency| CpuProfilingParams { seconds, frequency };
+        assert!(params(10, 99).validate().is_ok());
+        assert!(params(60, 1000).validate().is_ok());
+        assert!(params(0, 99).validate().is_err());
+        assert!(params(1_000_000, 99).validate().is_err());
+        assert!(params(10, 0).validate().is_err());
+        assert!(params(10, -1).validate().is_err());
+        assert!(params(10, 100_000).validate().is_err());
+    }
+
+    #[test]
+    fn test_admin_pause_resume() {
+        use warp::Reply;
+
+        let status = |admin_token, authorization, method, action| {
+            admin_response(admin_token, authorization, &method, action)
+                .into_response()
+                .status()
+        };
+        let auth = Some("Bearer secret");
+
+        assert!(constant_time_eq(b"Bearer secret", b"Bearer secret"));
+        assert!(!constant_time_eq(b"Bearer secret", b"Bearer secreT"));
+        assert!(!constant_time_eq(b"Bearer secret", b"Bearer secret2"));
+        assert!(!constant_time_eq(b"", b"Bearer secret"));
+
+        // Profiling endpoints only require the token once one is configured
+        assert!(is_profiling_authorized(None, None));
+        assert!(is_profiling_authorized(Some("secret"), auth));
+        assert!(!is_profiling_authorized(Some("secret"), None));
+        assert!(!is_profiling_authorized(
+            Some("secret"),
+            Some("Bearer wrong")
+        ));
+
+        assert_eq!(
+            status(None, auth, Method::POST, "pause"),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status(Some("secret"), None, Method::POST, "pause"),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(Some("secret"), Some("Bearer wrong"), Method::POST, "pause"),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(Some("secret"), auth, Method::GET, "pause"),
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+        assert!(!is_indexing_paused());
+
+        assert_eq!(
+            status(Some("secret"), auth, Method::POST, "pause"),
+            StatusCode::OK
+        );
+        assert!(is_indexing_paused());
+        assert_eq!(
+            status(Some("secret"), auth, Method::GET, "status"),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(Some("secret"), auth, Method::POST, "resume"),
+            StatusCode::OK
+        );
+        assert!(!is_indexing_paused());
+    }
+
     #[test]
     fn verify_tool() {
         use clap::CommandFactory;